@@ -1,22 +1,61 @@
 //! Driver for the CF-RU5102 UHF RFID reader
 extern crate crc16;
-extern crate num_enum;
 extern crate serial;
 extern crate failure;
+#[cfg(test)]
+extern crate nix;
 
 pub mod error;
+pub mod security;
+pub mod transport;
 
 use crc16::{State, MCRF4XX};
-use num_enum::TryFromPrimitive;
 use serial::core::prelude::*;
-use std::convert::TryFrom;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::error::{Error, Result};
+use crate::transport::Transport;
 
-pub struct Reader {
-    port: serial::SystemPort,
+/// Read/write timeout used for the serial port unless overridden.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Upper bound, in multiples of the configured timeout, on how long
+/// `inventory_continuous` will wait for a reader to stop streaming frames.
+const CONTINUOUS_SCAN_DEADLINE: u32 = 1000;
+
+pub struct Reader<T: Transport> {
+    port: T,
     address: u8,
+    timeout: Duration,
+    /// Bytes already read off the port that belong to the frame *after* the
+    /// one `read_frame` last returned, e.g. because a resync over-read past
+    /// the end of the current frame while probing a bogus length byte.
+    leftover: Vec<u8>,
+}
+
+fn calculate_crc(data: &[u8]) -> u16 {
+    State::<MCRF4XX>::calculate(data)
+}
+
+/// Interpret a single-byte boolean flag in a response payload, as returned
+/// by `check_lock_6b`/`check_read_protect`/`check_eas`. A missing byte (a
+/// truncated response) reads as `false` rather than panicking.
+pub(crate) fn is_flag_set(data: &[u8]) -> bool {
+    data.first().is_some_and(|&b| b != 0)
+}
+
+/// Convert a `beep`/`off`/`on` phase duration into the centiseconds byte the
+/// reader expects, rejecting durations that don't fit in a single byte
+/// rather than silently truncating them.
+fn beep_centiseconds(ms: u16) -> Result<u8> {
+    let centiseconds = ms / 10;
+    if centiseconds > u8::MAX as u16 {
+        return Err(Error::Program(format!(
+            "Beep duration {}ms is too long; the reader accepts at most 2.55s per phase",
+            ms
+        )));
+    }
+    Ok(centiseconds as u8)
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -58,30 +97,60 @@ enum CommandType {
     AcoustoOpticControl = 0x33,
 }
 
-#[derive(Debug, Eq, PartialEq, TryFromPrimitive)]
-#[repr(u8)]
+#[derive(Debug, Eq, PartialEq)]
 pub enum ResponseStatus {
-    OK = 0x00,
-    ReturnBeforeInventoryFinished = 0x01,
-    ScanTimeOverflow = 0x02,
-    MoreData = 0x03,
-    ReaderFlashFull = 0x04,
-    AccessPasswordError = 0x05,
-    KillTagError = 0x09,
-    KillPasswordZero = 0x0A,
-    CommandNotSupported = 0x0B,
-
-    SaveFail = 0x13,
-    CannotAdjust = 0x14,
+    OK,
+    ReturnBeforeInventoryFinished,
+    ScanTimeOverflow,
+    MoreData,
+    ReaderFlashFull,
+    AccessPasswordError,
+    KillTagError,
+    KillPasswordZero,
+    CommandNotSupported,
+
+    SaveFail,
+    CannotAdjust,
 
     // TODO: there are more of these
-    CommandExecuteError = 0xF9,
-    PoorCommunication = 0xFA,
-    NoTags = 0xFB,
-    TagError = 0xFC,
-    WrongLength = 0xFD,
-    IllegalCommand = 0xFE,
-    ParameterError = 0xFF,
+    CommandExecuteError,
+    PoorCommunication,
+    NoTags,
+    TagError,
+    WrongLength,
+    IllegalCommand,
+    ParameterError,
+
+    /// A status byte the driver doesn't recognise. Keeping this around
+    /// rather than panicking lets the driver keep running against readers
+    /// with undocumented status codes.
+    Unknown(u8),
+}
+
+impl From<u8> for ResponseStatus {
+    fn from(byte: u8) -> ResponseStatus {
+        match byte {
+            0x00 => ResponseStatus::OK,
+            0x01 => ResponseStatus::ReturnBeforeInventoryFinished,
+            0x02 => ResponseStatus::ScanTimeOverflow,
+            0x03 => ResponseStatus::MoreData,
+            0x04 => ResponseStatus::ReaderFlashFull,
+            0x05 => ResponseStatus::AccessPasswordError,
+            0x09 => ResponseStatus::KillTagError,
+            0x0A => ResponseStatus::KillPasswordZero,
+            0x0B => ResponseStatus::CommandNotSupported,
+            0x13 => ResponseStatus::SaveFail,
+            0x14 => ResponseStatus::CannotAdjust,
+            0xF9 => ResponseStatus::CommandExecuteError,
+            0xFA => ResponseStatus::PoorCommunication,
+            0xFB => ResponseStatus::NoTags,
+            0xFC => ResponseStatus::TagError,
+            0xFD => ResponseStatus::WrongLength,
+            0xFE => ResponseStatus::IllegalCommand,
+            0xFF => ResponseStatus::ParameterError,
+            other => ResponseStatus::Unknown(other),
+        }
+    }
 }
 
 impl ResponseStatus {
@@ -111,7 +180,7 @@ impl Command {
         pkt.push(self.address);
         pkt.push(self.command as u8);
         pkt.append(&mut self.data.clone());
-        let crc = Reader::calculate_crc(&pkt);
+        let crc = calculate_crc(&pkt);
         pkt.push((crc & 0xFF) as u8);
         pkt.push(((crc >> 8) & 0xFF) as u8);
         pkt
@@ -128,10 +197,18 @@ struct Response {
 
 impl Response {
     fn from_bytes(bytes: &[u8]) -> Result<Response> {
-        assert_eq!(bytes[0] as usize, bytes.len() - 1);
+        // A truncated frame or a stray noise byte read as the length
+        // shouldn't panic the driver; report it so the caller can
+        // resynchronize instead.
+        if bytes.is_empty() || bytes[0] as usize != bytes.len() - 1 {
+            return Err(Error::Program("Malformed response frame".to_string()));
+        }
         let len = bytes.len();
+        if len < 6 {
+            return Err(Error::Program("Response frame too short".to_string()));
+        }
 
-        let crc = Reader::calculate_crc(&bytes[0..len - 2]);
+        let crc = calculate_crc(&bytes[0..len - 2]);
         let payload_crc: u16 = ((bytes[len - 1] as u16) << 8) + bytes[len - 2] as u16;
         if payload_crc != crc {
             return Err(Error::Program("Bad CRC".to_string()));
@@ -141,7 +218,7 @@ impl Response {
         Ok(Response {
             address: payload[0],
             command: payload[1],
-            status: ResponseStatus::try_from(payload[2]).unwrap(),
+            status: ResponseStatus::from(payload[2]),
             data: payload[3..].to_vec(),
         })
     }
@@ -151,7 +228,8 @@ impl Response {
 pub struct ReaderInformation {
     version: Vec<u8>,
     reader_type: u8,
-    supported_protocols: u8,
+    /// Bitmask of tag air protocols the reader supports, e.g. EPC C1 G2 and ISO18000-6B.
+    pub supported_protocols: u8,
     max_freq: u8,
     min_freq: u8,
     power: u8,
@@ -166,6 +244,28 @@ pub enum MemoryLocation {
     User = 0x03,
 }
 
+/// Baud rates accepted by `Reader::set_baud_rate`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Baud {
+    Baud9600 = 0x00,
+    Baud19200 = 0x01,
+    Baud38400 = 0x02,
+    Baud57600 = 0x03,
+    Baud115200 = 0x04,
+}
+
+impl Baud {
+    fn to_serial(self) -> serial::BaudRate {
+        match self {
+            Baud::Baud9600 => serial::Baud9600,
+            Baud::Baud19200 => serial::Baud19200,
+            Baud::Baud38400 => serial::Baud38400,
+            Baud::Baud57600 => serial::Baud57600,
+            Baud::Baud115200 => serial::Baud115200,
+        }
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct ReadCommand {
     pub epc: Vec<u8>,
@@ -262,6 +362,43 @@ impl KillCommand {
     }
 }
 
+/// ISO18000-6B addresses tag memory in single bytes rather than the
+/// 2-byte words used by the EPC C1 G2 commands above.
+#[derive(PartialEq, Debug)]
+pub struct ReadCommand6B {
+    pub uid: Vec<u8>,
+    pub start_address: u8,
+    pub length: u8,
+}
+
+impl ReadCommand6B {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        pkt.extend(self.uid.clone());
+        pkt.push(self.start_address);
+        pkt.push(self.length);
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct WriteCommand6B {
+    pub uid: Vec<u8>,
+    pub start_address: u8,
+    pub data: Vec<u8>,
+}
+
+impl WriteCommand6B {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        pkt.extend(self.uid.clone());
+        pkt.push(self.start_address);
+        pkt.push(self.data.len() as u8);
+        pkt.extend(self.data.clone());
+        pkt
+    }
+}
+
 impl ReaderInformation {
     fn from_bytes(bytes: &[u8]) -> ReaderInformation {
         assert_eq!(bytes.len(), 8);
@@ -277,8 +414,8 @@ impl ReaderInformation {
     }
 }
 
-impl Reader {
-    pub fn new(port: &str) -> Result<Reader> {
+impl Reader<serial::SystemPort> {
+    pub fn new(port: &str) -> Result<Reader<serial::SystemPort>> {
         let mut port = serial::open(port)
             .map_err(|e| format!("Unable to connect to serial port {}: {:?}", port, e))?;
         port.reconfigure(&|settings| {
@@ -291,33 +428,118 @@ impl Reader {
         })
         .map_err(|e| format!("Failed to configure serial port: {}", e))?;
 
-        port.set_timeout(Duration::from_millis(1000))
+        serial::core::SerialPort::set_timeout(&mut port, DEFAULT_TIMEOUT)
             .map_err(|e| format!("Failed to set serial port timeout: {}", e))?;
         Ok(Reader {
             port: port,
             address: 0,
+            timeout: DEFAULT_TIMEOUT,
+            leftover: Vec::new(),
         })
     }
+}
+
+impl<T: Transport> Reader<T> {
+    /// Build a `Reader` on top of an already-configured `Transport`, e.g. a
+    /// `MockTransport` in tests.
+    pub fn with_transport(port: T, address: u8) -> Reader<T> {
+        Reader {
+            port,
+            address,
+            timeout: DEFAULT_TIMEOUT,
+            leftover: Vec::new(),
+        }
+    }
+
+    /// Read one length-prefixed, CRC-checked frame off the port.
+    ///
+    /// A noisy link can desync framing (a dropped or inserted byte), which
+    /// would otherwise make every following read misparse the length byte.
+    /// If the bytes collected so far don't parse as a valid frame, discard
+    /// the leading byte and try again with whatever follows, bounded by the
+    /// port's configured timeout. Any bytes read past the end of the frame
+    /// that's eventually matched belong to whatever comes next, so they're
+    /// kept in `self.leftover` rather than dropped.
+    fn read_frame(&mut self) -> Result<Response> {
+        let deadline = Instant::now() + self.timeout;
+        let mut buf: Vec<u8> = std::mem::take(&mut self.leftover);
 
-    fn calculate_crc(data: &[u8]) -> u16 {
-        State::<MCRF4XX>::calculate(data)
+        loop {
+            if buf.is_empty() {
+                if Instant::now() > deadline {
+                    return Err(Error::Program(
+                        "Timed out resynchronizing with reader".to_string(),
+                    ));
+                }
+                let mut byte = [0u8; 1];
+                std::io::Read::read_exact(&mut self.port, &mut byte)?;
+                buf.push(byte[0]);
+            }
+
+            let frame_len = buf[0] as usize + 1;
+            while buf.len() < frame_len {
+                if Instant::now() > deadline {
+                    return Err(Error::Program(
+                        "Timed out resynchronizing with reader".to_string(),
+                    ));
+                }
+                let mut byte = [0u8; 1];
+                std::io::Read::read_exact(&mut self.port, &mut byte)?;
+                buf.push(byte[0]);
+            }
+
+            match Response::from_bytes(&buf[..frame_len]) {
+                Ok(response) => {
+                    self.leftover = buf.split_off(frame_len);
+                    return Ok(response);
+                }
+                Err(_) => {
+                    buf.remove(0);
+                }
+            }
+        }
     }
 
     fn send_receive(&mut self, cmd: Command) -> Result<Response> {
         let cmd_bytes = cmd.to_bytes();
         std::io::Write::write(&mut self.port, &cmd_bytes)?;
-        let mut len = [0u8; 1];
-        std::io::Read::read_exact(&mut self.port, &mut len)?;
-        let len = len[0];
-        let mut response: Vec<u8> = Vec::with_capacity(len as usize + 1);
-        response.push(len);
-        {
-            use std::io::Read;
-            let reference = self.port.by_ref();
-            reference.take(len as u64).read_to_end(&mut response)?;
+        self.read_frame()
+    }
+
+    /// Send a command and keep reading frames for as long as the reader
+    /// reports `ReturnBeforeInventoryFinished` or `MoreData`, calling
+    /// `on_frame` with each frame's data as it arrives. Returns the status
+    /// of the final frame. Bounded by a generous multiple of the port's
+    /// configured timeout so a reader that never stops streaming can't hang
+    /// the caller forever.
+    fn send_receive_multi<F: FnMut(&[u8])>(
+        &mut self,
+        cmd: Command,
+        mut on_frame: F,
+    ) -> Result<ResponseStatus> {
+        let cmd_bytes = cmd.to_bytes();
+        std::io::Write::write(&mut self.port, &cmd_bytes)?;
+
+        let deadline = Instant::now() + self.timeout * CONTINUOUS_SCAN_DEADLINE;
+        loop {
+            if Instant::now() > deadline {
+                return Err(Error::Program(
+                    "Reader never finished streaming inventory frames".to_string(),
+                ));
+            }
+
+            let response = self.read_frame()?;
+            match response.status {
+                ResponseStatus::ReturnBeforeInventoryFinished | ResponseStatus::MoreData => {
+                    on_frame(&response.data);
+                }
+                ResponseStatus::OK | ResponseStatus::ScanTimeOverflow | ResponseStatus::NoTags => {
+                    on_frame(&response.data);
+                    return Ok(response.status);
+                }
+                other => return Err(Error::from(other)),
+            }
         }
-        let response = Response::from_bytes(&response)?;
-        Ok(response)
     }
 
     /// Fetch information on the reader in a ReaderInformation structure
@@ -334,6 +556,106 @@ impl Reader {
         Ok(ReaderInformation::from_bytes(&response.data))
     }
 
+    /// Set the reader's RF output power, in dBm (0-30).
+    pub fn set_power(&mut self, power: u8) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::SetPower,
+            data: vec![power],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Set the maximum duration a single inventory scan is allowed to run
+    /// for. The reader only accepts whole deciseconds up to 25.5s.
+    pub fn set_scan_time(&mut self, time: Duration) -> Result<()> {
+        let deciseconds = time.as_millis() / 100;
+        if deciseconds > u8::MAX as u128 {
+            return Err(Error::Program(format!(
+                "Scan time {:?} is too long; the reader accepts at most 25.5s",
+                time
+            )));
+        }
+        let deciseconds = deciseconds as u8;
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::SetScanTime,
+            data: vec![deciseconds],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Set the reader's operating frequency region.
+    pub fn set_region(&mut self, max_freq: u8, min_freq: u8) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::SetRegion,
+            data: vec![max_freq, min_freq],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Set the reader's RS-485 address, used to address this reader in all
+    /// subsequent commands.
+    pub fn set_address(&mut self, address: u8) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::SetAddress,
+            data: vec![address],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        self.address = address;
+        Ok(())
+    }
+
+    /// Set the reader's serial baud rate, reconfiguring the open port to
+    /// match once the reader has acknowledged the change.
+    pub fn set_baud_rate(&mut self, baud: Baud) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::SetBaudRate,
+            data: vec![baud as u8],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        self.port.set_baud_rate(baud)
+    }
+
+    /// Drive the reader's buzzer/LED `count` times, on for `on_ms` and off
+    /// for `off_ms` milliseconds each cycle. The reader only accepts whole
+    /// centiseconds up to 2.55s per phase.
+    pub fn beep(&mut self, count: u8, on_ms: u16, off_ms: u16) -> Result<()> {
+        let on_centiseconds = beep_centiseconds(on_ms)?;
+        let off_centiseconds = beep_centiseconds(off_ms)?;
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::AcoustoOpticControl,
+            data: vec![count, on_centiseconds, off_centiseconds],
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
     /// Inventory all tags in the reader's range.
     ///
     /// Returns a vector of tag IDs.
@@ -365,6 +687,33 @@ impl Reader {
         Ok(tags)
     }
 
+    /// Run an inventory scan that may span several response frames,
+    /// invoking `on_tag` with each tag's UID as it streams in rather than
+    /// waiting for the whole scan to finish.
+    pub fn inventory_continuous<F: FnMut(&[u8])>(&mut self, mut on_tag: F) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::Inventory,
+            data: Vec::new(),
+        };
+
+        self.send_receive_multi(cmd, |data| {
+            if data.is_empty() {
+                return;
+            }
+            let num_tags = data[0];
+            let mut offset = 1;
+            for _i in 0..num_tags {
+                let tag_len = data[offset];
+                offset += 1;
+                on_tag(&data[offset..(offset + tag_len as usize)]);
+                offset += tag_len as usize;
+            }
+        })?;
+
+        Ok(())
+    }
+
     pub fn read_data(&mut self, read_cmd: ReadCommand) -> Result<Vec<u8>> {
         let cmd = Command {
             address: self.address,
@@ -409,11 +758,109 @@ impl Reader {
         }
         Ok(())
     }
+
+    /// Inventory all ISO18000-6B tags in the reader's range.
+    ///
+    /// Returns a vector of 8-byte UIDs.
+    pub fn inventory_6b(&mut self) -> Result<Vec<Vec<u8>>> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::InventoryMultiple6B,
+            data: Vec::new(),
+        };
+        let response = self.send_receive(cmd)?;
+
+        if response.status == ResponseStatus::NoTags {
+            return Ok(vec![]);
+        } else if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+
+        let num_tags = *response
+            .data
+            .first()
+            .ok_or_else(|| Error::Program("Truncated inventory_6b response".to_string()))?;
+        let mut offset = 1;
+        let mut tags = Vec::new();
+
+        for _i in 0..num_tags {
+            let tag = response
+                .data
+                .get(offset..(offset + 8))
+                .ok_or_else(|| Error::Program("Truncated inventory_6b response".to_string()))?;
+            tags.push(tag.to_vec());
+            offset += 8;
+        }
+
+        Ok(tags)
+    }
+
+    pub fn read_data_6b(&mut self, read_cmd: ReadCommand6B) -> Result<Vec<u8>> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::ReadData6B,
+            data: read_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+
+        Ok(response.data)
+    }
+
+    pub fn write_data_6b(&mut self, write_cmd: WriteCommand6B) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::WriteData6B,
+            data: write_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+
+        Ok(())
+    }
+
+    /// Permanently lock an ISO18000-6B tag's UID memory.
+    pub fn lock_6b(&mut self, uid: Vec<u8>) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::Lock6B,
+            data: uid,
+        };
+        let response = self.send_receive(cmd)?;
+
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+
+        Ok(())
+    }
+
+    /// Check whether an ISO18000-6B tag's UID memory is locked.
+    pub fn check_lock_6b(&mut self, uid: Vec<u8>) -> Result<bool> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::CheckLock6B,
+            data: uid,
+        };
+        let response = self.send_receive(cmd)?;
+
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+
+        Ok(is_flag_set(&response.data))
+    }
 }
 
 #[test]
 fn test_crc() {
-    assert_eq!(Reader::calculate_crc(b"abcdef"), 64265)
+    assert_eq!(calculate_crc(b"abcdef"), 64265)
 }
 
 #[test]
@@ -441,3 +888,286 @@ fn test_response() {
         }
     );
 }
+
+#[test]
+fn test_inventory_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=Inventory, status=OK, num_tags=1, tag_len=4, tag=DEADBEEF
+    let response = vec![11, 0, 0x01, 0x00, 1, 4, 0xDE, 0xAD, 0xBE, 0xEF, 86, 48];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let tags = reader.inventory().unwrap();
+    assert_eq!(tags, vec![vec![0xDE, 0xAD, 0xBE, 0xEF]]);
+}
+
+#[test]
+fn test_unknown_status() {
+    assert_eq!(ResponseStatus::from(0x42), ResponseStatus::Unknown(0x42));
+}
+
+#[test]
+fn test_read_frame_resyncs_after_noise() {
+    use crate::transport::MockTransport;
+
+    // A stray noise byte precedes an otherwise valid NoTags response frame.
+    let noisy = vec![0x00, 5, 0, 1, 251, 242, 61];
+    let transport = MockTransport::new(vec![noisy]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let tags = reader.inventory().unwrap();
+    assert_eq!(tags, Vec::<Vec<u8>>::new());
+}
+
+#[test]
+fn test_read_frame_preserves_overread_bytes_for_next_frame() {
+    use crate::transport::MockTransport;
+
+    // A noise byte of 8 makes the resync loop read past the end of the
+    // first real frame, swallowing the first two bytes of the next one
+    // before the CRC check rejects the over-read and a single byte gets
+    // discarded to try again. Those swallowed bytes must come back for the
+    // following `read_frame` call rather than being lost.
+    let frame_a = vec![5, 0, 1, 251, 242, 61];
+    let frame_b = vec![5, 0, 1, 251, 242, 61];
+    let mut stream = vec![8];
+    stream.extend(frame_a);
+    stream.extend(frame_b);
+
+    let transport = MockTransport::new(vec![stream]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    assert_eq!(reader.inventory().unwrap(), Vec::<Vec<u8>>::new());
+    assert_eq!(reader.inventory().unwrap(), Vec::<Vec<u8>>::new());
+}
+
+#[test]
+fn test_set_scan_time_rejects_overlong_duration() {
+    use crate::transport::MockTransport;
+
+    // 30s is 300 deciseconds, which doesn't fit in the single byte the
+    // reader expects; this must be rejected rather than silently
+    // truncated to `300 % 256` deciseconds.
+    let transport = MockTransport::new(vec![]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    assert!(reader.set_scan_time(Duration::from_secs(30)).is_err());
+}
+
+#[test]
+fn test_read_command_6b() {
+    assert_eq!(
+        ReadCommand6B {
+            uid: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            start_address: 0x10,
+            length: 4,
+        }
+        .to_bytes(),
+        [1, 2, 3, 4, 5, 6, 7, 8, 0x10, 4]
+    );
+}
+
+#[test]
+fn test_write_command_6b() {
+    assert_eq!(
+        WriteCommand6B {
+            uid: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            start_address: 0x20,
+            data: vec![0xAA, 0xBB],
+        }
+        .to_bytes(),
+        [1, 2, 3, 4, 5, 6, 7, 8, 0x20, 2, 0xAA, 0xBB]
+    );
+}
+
+#[test]
+fn test_inventory_6b_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=InventoryMultiple6B, status=OK, num_tags=2,
+    // uid1=0x0101010101010101, uid2=0x0202020202020202
+    let response = vec![
+        22, 0, 0x51, 0, 2, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 25, 195,
+    ];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let tags = reader.inventory_6b().unwrap();
+    assert_eq!(tags, vec![vec![1; 8], vec![2; 8]]);
+}
+
+#[test]
+fn test_inventory_6b_rejects_truncated_response_instead_of_panicking() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=InventoryMultiple6B, status=OK, num_tags=2, but
+    // only one 8-byte tag's worth of data actually follows.
+    let response = vec![14, 0, 0x51, 0, 2, 1, 1, 1, 1, 1, 1, 1, 1, 138, 45];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    assert!(reader.inventory_6b().is_err());
+}
+
+#[test]
+fn test_read_data_6b_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=ReadData6B, status=OK, data=AABBCCDD
+    let response = vec![9, 0, 0x52, 0, 0xAA, 0xBB, 0xCC, 0xDD, 173, 43];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let data = reader
+        .read_data_6b(ReadCommand6B {
+            uid: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            start_address: 0x10,
+            length: 4,
+        })
+        .unwrap();
+    assert_eq!(data, vec![0xAA, 0xBB, 0xCC, 0xDD]);
+}
+
+#[test]
+fn test_check_lock_6b_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=CheckLock6B, status=OK, data=[1] (locked)
+    let response = vec![6, 0, 0x54, 0, 1, 27, 250];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let uid = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let locked = reader.check_lock_6b(uid.clone()).unwrap();
+    assert!(locked);
+    assert_eq!(reader.port.sent, Command {
+        address: 0,
+        command: CommandType::CheckLock6B,
+        data: uid,
+    }.to_bytes());
+}
+
+#[test]
+fn test_set_power_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=SetPower, status=OK
+    let response = vec![5, 0, 0x2F, 0, 141, 205];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader.set_power(20).unwrap();
+    assert_eq!(
+        reader.port.sent,
+        Command {
+            address: 0,
+            command: CommandType::SetPower,
+            data: vec![20],
+        }
+        .to_bytes()
+    );
+}
+
+#[test]
+fn test_set_region_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=SetRegion, status=OK
+    let response = vec![5, 0, 0x22, 0, 245, 125];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader.set_region(0xFF, 0x00).unwrap();
+    assert_eq!(
+        reader.port.sent,
+        Command {
+            address: 0,
+            command: CommandType::SetRegion,
+            data: vec![0xFF, 0x00],
+        }
+        .to_bytes()
+    );
+}
+
+#[test]
+fn test_set_address_round_trip_updates_reader_address() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=SetAddress, status=OK
+    let response = vec![5, 0, 0x24, 0, 37, 41];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader.set_address(5).unwrap();
+    assert_eq!(reader.address, 5);
+}
+
+#[test]
+fn test_beep_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=AcoustoOpticControl, status=OK
+    let response = vec![5, 0, 0x33, 0, 188, 241];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader.beep(3, 200, 100).unwrap();
+    assert_eq!(
+        reader.port.sent,
+        Command {
+            address: 0,
+            command: CommandType::AcoustoOpticControl,
+            data: vec![3, 20, 10],
+        }
+        .to_bytes()
+    );
+}
+
+#[test]
+fn test_beep_rejects_overlong_phase_duration() {
+    use crate::transport::MockTransport;
+
+    // 2600ms is 260 centiseconds, which doesn't fit in the single byte the
+    // reader expects; this must be rejected rather than silently truncated
+    // to `260 % 256` centiseconds.
+    let transport = MockTransport::new(vec![]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    assert!(reader.beep(3, 2600, 100).is_err());
+}
+
+#[test]
+fn test_reader_information_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=GetReaderInformation, status=OK, data=01 02 03 04 05 06 07 08
+    let response = vec![13, 0, 0x21, 0, 1, 2, 3, 4, 5, 6, 7, 8, 219, 123];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let info = reader.reader_information().unwrap();
+    assert_eq!(info.supported_protocols, 4);
+}
+
+#[test]
+fn test_inventory_continuous_accumulates_across_frames() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=Inventory, status=ReturnBeforeInventoryFinished,
+    // num_tags=1, tag_len=4, tag=DEADBEEF
+    let frame1 = vec![11, 0, 1, 1, 1, 4, 0xDE, 0xAD, 0xBE, 0xEF, 131, 175];
+    // address=0, command=Inventory, status=OK, num_tags=1, tag_len=4, tag=CAFEBABE
+    let frame2 = vec![11, 0, 1, 0, 1, 4, 0xCA, 0xFE, 0xBA, 0xBE, 240, 201];
+
+    let transport = MockTransport::new(vec![frame1, frame2]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let mut tags = Vec::new();
+    reader.inventory_continuous(|tag| tags.push(tag.to_vec())).unwrap();
+
+    assert_eq!(
+        tags,
+        vec![vec![0xDE, 0xAD, 0xBE, 0xEF], vec![0xCA, 0xFE, 0xBA, 0xBE]]
+    );
+}
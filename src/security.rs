@@ -0,0 +1,516 @@
+//! Tag security operations: lock, block-erase, read-protect and EAS.
+use crate::error::{Error, Result};
+use crate::transport::Transport;
+use crate::{is_flag_set, Command, CommandType, MemoryLocation, Reader};
+
+/// The lock state to apply to a memory bank in a `LockCommand`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum LockAction {
+    Open = 0x00,
+    Locked = 0x01,
+    PermaLock = 0x02,
+    PermaOpen = 0x03,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct LockCommand {
+    pub epc: Vec<u8>,
+    pub location: MemoryLocation,
+    pub action: LockAction,
+    pub password: Option<Vec<u8>>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl LockCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        pkt.push(self.location as u8);
+        pkt.push(self.action as u8);
+        if let Some(p) = &self.password {
+            pkt.extend(p.clone());
+        } else {
+            pkt.extend(vec![0, 0, 0, 0]);
+        }
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct BlockEraseCommand {
+    pub epc: Vec<u8>,
+    pub location: MemoryLocation,
+    pub start_address: u8,
+    pub count: u8,
+    pub password: Option<Vec<u8>>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl BlockEraseCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        pkt.push(self.location as u8);
+        pkt.push(self.start_address);
+        pkt.push(self.count);
+        if let Some(p) = &self.password {
+            pkt.extend(p.clone());
+        } else {
+            pkt.extend(vec![0, 0, 0, 0]);
+        }
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ReadProtectCommand {
+    pub epc: Vec<u8>,
+    pub password: Option<Vec<u8>>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl ReadProtectCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        if let Some(p) = &self.password {
+            pkt.extend(p.clone());
+        } else {
+            pkt.extend(vec![0, 0, 0, 0]);
+        }
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct EASCommand {
+    pub epc: Vec<u8>,
+    pub password: Option<Vec<u8>>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl EASCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        if let Some(p) = &self.password {
+            pkt.extend(p.clone());
+        } else {
+            pkt.extend(vec![0, 0, 0, 0]);
+        }
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CheckReadProtectCommand {
+    pub epc: Vec<u8>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl CheckReadProtectCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CheckEASCommand {
+    pub epc: Vec<u8>,
+    pub mask_address: Option<u8>,
+    pub mask_length: Option<u8>,
+}
+
+impl CheckEASCommand {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut pkt: Vec<u8> = Vec::new();
+        // EPC size is in words, which are 2 bytes long
+        pkt.push(self.epc.len() as u8 / 2);
+        pkt.extend(self.epc.clone());
+        if let Some(addr) = self.mask_address {
+            pkt.push(addr);
+        }
+        if let Some(len) = self.mask_length {
+            pkt.push(len);
+        }
+        pkt
+    }
+}
+
+impl<T: Transport> Reader<T> {
+    /// Lock, permalock or unlock a tag's memory bank.
+    pub fn lock(&mut self, lock_cmd: LockCommand) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::Lock,
+            data: lock_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Erase a range of words from a tag's memory bank.
+    pub fn block_erase(&mut self, erase_cmd: BlockEraseCommand) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::BlockErase,
+            data: erase_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Enable read protection on a tag.
+    pub fn set_read_protect(&mut self, protect_cmd: ReadProtectCommand) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::ReadProtect,
+            data: protect_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Clear read protection on a tag.
+    pub fn reset_read_protect(&mut self, protect_cmd: ReadProtectCommand) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::ResetReadProtect,
+            data: protect_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Check whether a tag currently has read protection enabled.
+    pub fn check_read_protect(&mut self, check_cmd: CheckReadProtectCommand) -> Result<bool> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::CheckReadProtect,
+            data: check_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(is_flag_set(&response.data))
+    }
+
+    /// Arm electronic article surveillance (EAS) on a tag.
+    pub fn set_eas(&mut self, eas_cmd: EASCommand) -> Result<()> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::EASAlarm,
+            data: eas_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(())
+    }
+
+    /// Check whether a tag currently has EAS armed.
+    pub fn check_eas(&mut self, check_cmd: CheckEASCommand) -> Result<bool> {
+        let cmd = Command {
+            address: self.address,
+            command: CommandType::CheckEASAlarm,
+            data: check_cmd.to_bytes(),
+        };
+        let response = self.send_receive(cmd)?;
+        if !response.status.is_success() {
+            return Err(Error::from(response.status));
+        }
+        Ok(is_flag_set(&response.data))
+    }
+}
+
+#[test]
+fn test_lock_command() {
+    assert_eq!(
+        LockCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            location: MemoryLocation::EPC,
+            action: LockAction::Locked,
+            password: None,
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x01, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_block_erase_command() {
+    assert_eq!(
+        BlockEraseCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            location: MemoryLocation::User,
+            start_address: 2,
+            count: 1,
+            password: Some(vec![1, 2, 3, 4]),
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF, 0x03, 2, 1, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_read_protect_command() {
+    assert_eq!(
+        ReadProtectCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            password: Some(vec![1, 2, 3, 4]),
+            mask_address: Some(0),
+            mask_length: Some(16),
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF, 1, 2, 3, 4, 0, 16]
+    );
+}
+
+#[test]
+fn test_read_protect_command_defaults_missing_password_to_zero() {
+    assert_eq!(
+        ReadProtectCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            password: None,
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_eas_command() {
+    assert_eq!(
+        EASCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            password: Some(vec![1, 2, 3, 4]),
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_check_read_protect_command() {
+    assert_eq!(
+        CheckReadProtectCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF]
+    );
+}
+
+#[test]
+fn test_check_eas_command() {
+    assert_eq!(
+        CheckEASCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            mask_address: None,
+            mask_length: None,
+        }
+        .to_bytes(),
+        [2, 0xDE, 0xAD, 0xBE, 0xEF]
+    );
+}
+
+#[test]
+fn test_lock_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=Lock, status=OK
+    let response = vec![5, 0, 0x06, 0, 166, 57];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader
+        .lock(LockCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            location: MemoryLocation::EPC,
+            action: LockAction::Locked,
+            password: None,
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_block_erase_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=BlockErase, status=OK
+    let response = vec![5, 0, 0x07, 0, 126, 32];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader
+        .block_erase(BlockEraseCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            location: MemoryLocation::User,
+            start_address: 2,
+            count: 1,
+            password: None,
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_set_and_reset_read_protect_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=ReadProtect, status=OK
+    // address=0, command=ResetReadProtect, status=OK
+    let responses = vec![vec![5, 0, 0x08, 0, 182, 163], vec![5, 0, 0x0a, 0, 6, 144]];
+    let transport = MockTransport::new(responses);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let epc = vec![0xDE, 0xAD, 0xBE, 0xEF];
+    let password = Some(vec![1, 2, 3, 4]);
+    reader
+        .set_read_protect(ReadProtectCommand {
+            epc: epc.clone(),
+            password: password.clone(),
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+    reader
+        .reset_read_protect(ReadProtectCommand {
+            epc,
+            password,
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_check_read_protect_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=CheckReadProtect, status=OK, data=[1] (protected)
+    let response = vec![6, 0, 0x0b, 0, 1, 63, 51];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let protected = reader
+        .check_read_protect(CheckReadProtectCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+    assert!(protected);
+}
+
+#[test]
+fn test_set_eas_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=EASAlarm, status=OK
+    let response = vec![5, 0, 0x0c, 0, 214, 196];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    reader
+        .set_eas(EASCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            password: Some(vec![1, 2, 3, 4]),
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_check_eas_round_trip() {
+    use crate::transport::MockTransport;
+
+    // address=0, command=CheckEASAlarm, status=OK, data=[1] (armed)
+    let response = vec![6, 0, 0x0d, 0, 1, 230, 229];
+    let transport = MockTransport::new(vec![response]);
+    let mut reader = Reader::with_transport(transport, 0);
+
+    let armed = reader
+        .check_eas(CheckEASCommand {
+            epc: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            mask_address: None,
+            mask_length: None,
+        })
+        .unwrap();
+    assert!(armed);
+}
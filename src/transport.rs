@@ -0,0 +1,208 @@
+//! The byte-level link a `Reader` talks over, abstracted so the protocol
+//! logic in `lib.rs` can run against a real serial port, an in-memory mock
+//! for unit tests, or a PTY-backed fake reader for unit tests.
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::Baud;
+
+/// A blocking, timeout-capable byte stream a `Reader` can be built on top of.
+pub trait Transport: Read + Write {
+    /// Set the read/write timeout used by `Reader::send_receive`.
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()>;
+
+    /// Reconfigure the link to a new baud rate. Transports that have no
+    /// concept of baud rate (e.g. an in-memory mock) can leave this a no-op.
+    fn set_baud_rate(&mut self, _baud: Baud) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for serial::SystemPort {
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        use serial::core::SerialPort;
+        SerialPort::set_timeout(self, timeout)
+            .map_err(|e| format!("Failed to set serial port timeout: {}", e).into())
+    }
+
+    fn set_baud_rate(&mut self, baud: Baud) -> Result<()> {
+        use serial::core::SerialPort;
+        let rate = baud.to_serial();
+        self.reconfigure(&|settings| settings.set_baud_rate(rate))
+            .map_err(|e| format!("Failed to configure serial port: {}", e).into())
+    }
+}
+
+/// A `Transport` backed by in-memory request/response queues, for testing
+/// command/response round-trips without real hardware.
+///
+/// Each call to `inventory()`/`read_data()`/etc. writes one framed command
+/// and reads one framed response; `MockTransport` hands back the queued
+/// responses in order and records every frame written to it.
+pub struct MockTransport {
+    responses: std::collections::VecDeque<u8>,
+    /// Every byte the driver has written to this transport, in order.
+    pub sent: Vec<u8>,
+}
+
+impl MockTransport {
+    /// Build a mock transport that will hand back `responses` in order.
+    /// The responses are concatenated into a single byte stream, so one
+    /// `read` can return bytes from more than one response.
+    pub fn new(responses: Vec<Vec<u8>>) -> MockTransport {
+        let mut queue = std::collections::VecDeque::new();
+        for response in responses {
+            queue.extend(response);
+        }
+        MockTransport {
+            responses: queue,
+            sent: Vec::new(),
+        }
+    }
+}
+
+impl Read for MockTransport {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.responses.pop_front() {
+                Some(byte) => {
+                    buf[n] = byte;
+                    n += 1;
+                }
+                None => break,
+            }
+        }
+        if n == 0 && !buf.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "no more mock responses queued",
+            ));
+        }
+        Ok(n)
+    }
+}
+
+impl Write for MockTransport {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sent.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for MockTransport {
+    fn set_timeout(&mut self, _timeout: Duration) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A `Transport` backed by a pseudo-terminal pair, letting a test run a
+/// fake reader on the slave side and drive the real `Reader` protocol code
+/// against it over the master side. This module is only compiled for the
+/// crate's own unit tests; it is not available to integration tests.
+#[cfg(test)]
+pub mod pty {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use std::time::Duration;
+
+    use super::Transport;
+    use crate::error::Result;
+
+    /// The master side of a PTY pair, suitable for use as a `Reader`'s
+    /// transport. Whatever opens the returned slave path plays the role of
+    /// the reader hardware.
+    pub struct PtyTransport {
+        master: File,
+    }
+
+    impl PtyTransport {
+        /// Open a new PTY pair, returning the master-side transport and the
+        /// path to the slave device.
+        pub fn open() -> nix::Result<(PtyTransport, String)> {
+            let pty = nix::pty::openpty(None, None)?;
+            let path = unsafe {
+                std::ffi::CStr::from_ptr(nix::libc::ptsname(pty.master))
+                    .to_string_lossy()
+                    .into_owned()
+            };
+
+            // Our framed binary protocol has no line terminator, so the
+            // slave's line discipline must be raw: otherwise the kernel
+            // buffers input until it sees a newline and reads never return.
+            let mut termios = nix::sys::termios::tcgetattr(pty.slave)?;
+            nix::sys::termios::cfmakeraw(&mut termios);
+            nix::sys::termios::tcsetattr(pty.slave, nix::sys::termios::SetArg::TCSANOW, &termios)?;
+            nix::unistd::close(pty.slave)?;
+
+            Ok((
+                PtyTransport {
+                    master: unsafe { File::from_raw_fd(pty.master) },
+                },
+                path,
+            ))
+        }
+    }
+
+    impl Read for PtyTransport {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.master.read(buf)
+        }
+    }
+
+    impl Write for PtyTransport {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.master.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.master.flush()
+        }
+    }
+
+    impl Transport for PtyTransport {
+        fn set_timeout(&mut self, _timeout: Duration) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pty_inventory_round_trip() {
+        use std::fs::OpenOptions;
+        use std::thread;
+
+        use crate::Reader;
+
+        let (transport, slave_path) = PtyTransport::open().expect("failed to open pty");
+        let mut slave = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&slave_path)
+            .expect("failed to open pty slave");
+
+        // Play the role of the reader hardware: read the inventory command
+        // the driver sends and write back a canned NoTags response.
+        let fake_reader = thread::spawn(move || {
+            // address=0, command=Inventory, no data
+            let mut cmd = [0u8; 5];
+            slave.read_exact(&mut cmd).unwrap();
+            assert_eq!(cmd, [4, 0, 0x01, 219, 75]);
+
+            // address=0, command=Inventory, status=NoTags
+            let response = [5u8, 0, 1, 251, 242, 61];
+            slave.write_all(&response).unwrap();
+        });
+
+        let mut reader = Reader::with_transport(transport, 0);
+        let tags = reader.inventory().unwrap();
+        assert_eq!(tags, Vec::<Vec<u8>>::new());
+
+        fake_reader.join().unwrap();
+    }
+}